@@ -2,9 +2,14 @@ use bevy::ecs::all_tuples;
 use bevy::ecs::system::SystemState;
 use bevy::prelude::*;
 
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::fmt;
+
 use bevy::ecs::component::ComponentId;
 use bevy::ecs::query::ReadOnlyWorldQuery;
-use bevy::reflect::TypeRegistry;
+use bevy::ecs::reflect::ReflectResource;
+use bevy::reflect::{ReflectMut, TypeRegistry};
 use bevy::scene::DynamicEntity;
 use bevy::utils::{HashMap, HashSet};
 
@@ -114,6 +119,141 @@ enum ComponentSelection {
     ByIds(HashSet<ComponentId>),
 }
 
+/// Compute the `ComponentId`s of the components that are excluded from
+/// [`ComponentSelection::All`] extraction by default.
+///
+/// The list is `GlobalTransform` and `ComputedVisibility`: computed data that
+/// gets recomputed at runtime and should not normally end up in a serialized
+/// scene. No other components (GPU handles, runtime caches, ...) are in it.
+///
+/// Components that aren't registered in the `World` are simply skipped.
+fn default_denied_component_ids(world: &World) -> HashSet<ComponentId> {
+    let mut deny = HashSet::default();
+
+    macro_rules! deny_if_present {
+        ($($t:ty),* $(,)?) => {
+            $(
+                if let Some(id) = world.component_id::<$t>() {
+                    deny.insert(id);
+                }
+            )*
+        };
+    }
+
+    deny_if_present!(GlobalTransform, ComputedVisibility);
+
+    deny
+}
+
+/// Recursively replace every `Entity` reflected inside `value` that is not
+/// in `known` with `Entity::PLACEHOLDER`
+///
+/// Structs, tuple structs, tuples, lists, arrays, enums and map values are
+/// walked; map keys and reflect-opaque values are not visited.
+///
+/// Used to scrub references to entities that fell outside the selection of
+/// a [`SceneBuilder`] when [`drop_external_entity_refs`][SceneBuilder::drop_external_entity_refs]
+/// is set.
+fn scrub_external_entities(value: &mut dyn Reflect, known: &HashSet<Entity>) {
+    if let Some(entity) = value.downcast_mut::<Entity>() {
+        if !known.contains(entity) {
+            *entity = Entity::PLACEHOLDER;
+        }
+        return;
+    }
+
+    match value.reflect_mut() {
+        ReflectMut::Struct(s) => {
+            for i in 0..s.field_len() {
+                if let Some(field) = s.field_at_mut(i) {
+                    scrub_external_entities(field, known);
+                }
+            }
+        }
+        ReflectMut::TupleStruct(s) => {
+            for i in 0..s.field_len() {
+                if let Some(field) = s.field_mut(i) {
+                    scrub_external_entities(field, known);
+                }
+            }
+        }
+        ReflectMut::Tuple(t) => {
+            for i in 0..t.field_len() {
+                if let Some(field) = t.field_mut(i) {
+                    scrub_external_entities(field, known);
+                }
+            }
+        }
+        ReflectMut::List(l) => {
+            for i in 0..l.len() {
+                if let Some(item) = l.get_mut(i) {
+                    scrub_external_entities(item, known);
+                }
+            }
+        }
+        ReflectMut::Array(a) => {
+            for i in 0..a.len() {
+                if let Some(item) = a.get_mut(i) {
+                    scrub_external_entities(item, known);
+                }
+            }
+        }
+        ReflectMut::Map(m) => {
+            for i in 0..m.len() {
+                if let Some((_, v)) = m.get_at_mut(i) {
+                    scrub_external_entities(v, known);
+                }
+            }
+        }
+        ReflectMut::Enum(e) => {
+            for i in 0..e.field_len() {
+                if let Some(field) = e.field_at_mut(i) {
+                    scrub_external_entities(field, known);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Error returned when a component requested by name or `TypeId` could not
+/// be resolved against the `TypeRegistry` and/or the `World`'s `Components`,
+/// or when a resource requested by name could not be resolved against the
+/// `TypeRegistry`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UnknownComponent {
+    /// No type with this type path is registered in the `TypeRegistry`.
+    Name(String),
+    /// No type with this `TypeId` is registered in the `TypeRegistry`.
+    TypeId(TypeId),
+    /// The type is registered, but has no component of that type in the `World`.
+    NotAComponent(TypeId),
+    /// The type is registered, but has no `ReflectResource` type data, so it
+    /// cannot be extracted as a resource.
+    NotAResource(TypeId),
+}
+
+impl fmt::Display for UnknownComponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnknownComponent::Name(name) => {
+                write!(f, "no type with type path \"{}\" is registered", name)
+            }
+            UnknownComponent::TypeId(id) => {
+                write!(f, "no type with TypeId {:?} is registered", id)
+            }
+            UnknownComponent::NotAComponent(id) => {
+                write!(f, "type with TypeId {:?} is not a component in the World", id)
+            }
+            UnknownComponent::NotAResource(id) => {
+                write!(f, "type with TypeId {:?} has no ReflectResource type data", id)
+            }
+        }
+    }
+}
+
+impl std::error::Error for UnknownComponent {}
+
 /// Flexible tool for creating Bevy scenes
 ///
 /// You can select what entities from your `World` you would like
@@ -130,6 +270,21 @@ enum ComponentSelection {
 pub struct SceneBuilder<'w> {
     world: &'w mut World,
     ec: HashMap<Entity, ComponentSelection>,
+    /// Built-in default deny-list (see [`default_denied_component_ids`]); only
+    /// applied to `ComponentSelection::All` extractions.
+    default_deny: HashSet<ComponentId>,
+    /// Components denied via [`deny_component`][SceneBuilder::deny_component] /
+    /// [`deny_components`][SceneBuilder::deny_components]; applied to both
+    /// `ComponentSelection::All` and explicit `ByIds` selections, since the
+    /// caller asked for these specific components to never be included.
+    explicit_deny: HashSet<ComponentId>,
+    keep_external_refs: bool,
+    resources: HashSet<TypeId>,
+    /// Resources denied via [`deny_resource`][SceneBuilder::deny_resource];
+    /// subtracted from `resources` in
+    /// [`build_scene_with_resources`][SceneBuilder::build_scene_with_resources].
+    deny_resources: HashSet<TypeId>,
+    component_filter: Option<RefCell<Box<dyn FnMut(&World, Entity, ComponentId) -> bool>>>,
 }
 
 impl<'w> SceneBuilder<'w> {
@@ -137,11 +292,179 @@ impl<'w> SceneBuilder<'w> {
     ///
     /// The entities and components of the created scene will come from
     /// the provided `world`.
+    ///
+    /// A small set of default-denied components (computed transforms,
+    /// visibility caches, ...) is preconfigured; see
+    /// [`deny_component`][Self::deny_component] and
+    /// [`clear_default_filters`][Self::clear_default_filters].
     pub fn new(world: &'w mut World) -> SceneBuilder<'w> {
+        let default_deny = default_denied_component_ids(world);
         SceneBuilder {
             world,
             ec: Default::default(),
+            default_deny,
+            explicit_deny: Default::default(),
+            keep_external_refs: true,
+            resources: Default::default(),
+            deny_resources: Default::default(),
+            component_filter: None,
+        }
+    }
+
+    /// Add every entity in the `World` for which `pred` returns `true`
+    ///
+    /// This gives the builder the dynamic, push-based flexibility of an
+    /// arbitrary predicate, for selections that can't be expressed with a
+    /// static query filter type (e.g. "`Health.current < 10`"). All
+    /// components of each matching entity will be included.
+    ///
+    /// If you want to only include specific components, try combining this
+    /// with [`retain_components`][Self::retain_components].
+    pub fn add_where<F>(&mut self, mut pred: F) -> &mut Self
+    where
+        F: FnMut(&World, Entity) -> bool,
+    {
+        let candidates: Vec<Entity> = self.world.archetypes().iter()
+            .flat_map(|a| a.entities().iter().map(|ae| ae.entity()))
+            .collect();
+        for e in candidates {
+            if pred(self.world, e) {
+                self.ec.insert(e, ComponentSelection::All);
+            }
+        }
+        self
+    }
+
+    /// Run a predicate over every component about to be extracted during
+    /// [`build_scene`][Self::build_scene]
+    ///
+    /// `pred` is called with the entity and component id being considered,
+    /// for every entity added to the builder, after the deny-list filter;
+    /// components for which it returns `false` are left out of the scene.
+    pub fn retain_components<F>(&mut self, pred: F) -> &mut Self
+    where
+        F: FnMut(&World, Entity, ComponentId) -> bool + 'static,
+    {
+        self.component_filter = Some(RefCell::new(Box::new(pred)));
+        self
+    }
+
+    fn retain_component(&self, entity: Entity, id: ComponentId) -> bool {
+        match &self.component_filter {
+            Some(pred) => (pred.borrow_mut())(self.world, entity, id),
+            None => true,
+        }
+    }
+
+    /// Include a resource in the built scene
+    ///
+    /// `R` must be registered in the `TypeRegistry` with `ReflectResource`.
+    ///
+    /// If you only know the resource's type at runtime, try:
+    ///  - [`add_resource_by_name`][Self::add_resource_by_name]
+    ///
+    /// To exclude a resource instead, see [`deny_resource`][Self::deny_resource].
+    pub fn add_resource<R: Resource + Reflect>(&mut self) -> &mut Self {
+        self.resources.insert(TypeId::of::<R>());
+        self
+    }
+
+    /// Include a resource in the built scene, resolved by type path
+    ///
+    /// Works just like [`add_resource`][Self::add_resource], but resolves
+    /// `name` against the `TypeRegistry` at runtime instead of requiring the
+    /// resource's Rust type at compile time.
+    ///
+    /// Returns an error if `name` does not resolve to a registered type, or
+    /// if the type has no `ReflectResource` type data.
+    pub fn add_resource_by_name(&mut self, name: &str) -> Result<&mut Self, UnknownComponent> {
+        let registry = self.world.get_resource::<TypeRegistry>().unwrap().read();
+        let registration = registry
+            .get_with_type_path(name)
+            .ok_or_else(|| UnknownComponent::Name(name.to_string()))?;
+        registration
+            .data::<ReflectResource>()
+            .ok_or(UnknownComponent::NotAResource(registration.type_id()))?;
+        let type_id = registration.type_id();
+        drop(registry);
+        self.resources.insert(type_id);
+        Ok(self)
+    }
+
+    /// Exclude a resource from the built scene
+    ///
+    /// This overrides [`add_resource`][Self::add_resource] /
+    /// [`add_resource_by_name`][Self::add_resource_by_name]: a denied
+    /// resource is left out of [`build_scene_with_resources`][Self::build_scene_with_resources]
+    /// even if it was explicitly added. Unlike component selection, there is
+    /// no "include all resources" mode and no built-in default deny-list for
+    /// resources, so this only matters for resources you added yourself.
+    pub fn deny_resource<R: Resource>(&mut self) -> &mut Self {
+        self.deny_resources.insert(TypeId::of::<R>());
+        self
+    }
+
+    /// Exclude a component from the built scene entirely
+    ///
+    /// Unlike the built-in default deny-list (see
+    /// [`clear_default_filters`][Self::clear_default_filters]), this also
+    /// subtracts the component from explicit selections made via
+    /// [`add_components_to_entity`][Self::add_components_to_entity] and
+    /// similar methods: if you explicitly deny a component, asking for it by
+    /// name, `TypeId`, or `ComponentList` afterwards will not bring it back.
+    pub fn deny_component<T: Component>(&mut self) -> &mut Self {
+        if let Some(id) = self.world.component_id::<T>() {
+            self.explicit_deny.insert(id);
         }
+        self
+    }
+
+    /// Exclude a set of components from the built scene entirely
+    ///
+    /// See [`deny_component`][Self::deny_component] for details.
+    pub fn deny_components<Q: ComponentList>(&mut self) -> &mut Self {
+        Q::do_component_ids(self.world, &mut |id| {
+            self.explicit_deny.insert(id);
+        });
+        self
+    }
+
+    /// Remove the built-in default deny-list
+    ///
+    /// After calling this, `ComponentSelection::All` extractions will include
+    /// every component on the entity that isn't also excluded via
+    /// [`deny_component`][Self::deny_component] / [`deny_components`][Self::deny_components].
+    pub fn clear_default_filters(&mut self) -> &mut Self {
+        self.default_deny.clear();
+        self
+    }
+
+    /// Replace out-of-selection `Entity` references with `Entity::PLACEHOLDER` on build
+    ///
+    /// By default, [`build_scene`][Self::build_scene] never touches component
+    /// data: `Entity` fields (parents, children, targets, ...) are copied
+    /// as-is, including ones that point at entities outside the selection.
+    /// Call this method to have those external references replaced with
+    /// `Entity::PLACEHOLDER` instead, wherever they can be reached through
+    /// reflection (see the tradeoff below).
+    ///
+    /// Design note: this deliberately does not go through `ReflectMapEntities`
+    /// (as an `EntityMap`-based remap would). `ReflectMapEntities::map_entities`
+    /// operates on components already inserted in a `World`, which would force
+    /// every `build_scene` call through a throwaway scratch `World` again —
+    /// exactly the source of the nondeterministic ids, discarded `Result`s, and
+    /// `from_world`-panics-on-missing-resources that an earlier version of this
+    /// method had and was reverted for. Instead, [`scrub_external_entities`]
+    /// walks the already-cloned `Reflect` data directly, with no `World`
+    /// involved and no id renumbering; the tradeoff is that components without
+    /// a generically-walkable shape (e.g. reflect-opaque types) won't have
+    /// their internal entity references found this way, and `Entity` values
+    /// used as map keys are left as they are. Entity ids stay
+    /// verbatim either way — this method only ever changes the *value* of
+    /// out-of-selection `Entity` fields, never entity ids in the scene.
+    pub fn drop_external_entity_refs(&mut self) -> &mut Self {
+        self.keep_external_refs = false;
+        self
     }
 
     /// Add all entities that match the given query filter
@@ -178,6 +501,49 @@ impl<'w> SceneBuilder<'w> {
         self
     }
 
+    /// Add a specific entity, plus its whole `Children` subtree
+    ///
+    /// `e` and every entity transitively reachable from it through `Children`
+    /// are added, with all components included.
+    ///
+    /// Components on the selected entities may hold `Entity` fields that
+    /// point elsewhere in the subtree (parents, children, targets, ...), or
+    /// outside it entirely; see
+    /// [`drop_external_entity_refs`][Self::drop_external_entity_refs] for how
+    /// references outside the selection are handled.
+    pub fn add_entity_with_descendants(&mut self, e: Entity) -> &mut Self {
+        self.ec.insert(e, ComponentSelection::All);
+        let mut stack = vec![e];
+        while let Some(e) = stack.pop() {
+            if let Some(children) = self.world.get::<Children>(e) {
+                for &child in children.iter() {
+                    self.ec.insert(child, ComponentSelection::All);
+                    stack.push(child);
+                }
+            }
+        }
+        self
+    }
+
+    /// Add all entities that match the given query filter, plus their `Children` subtrees
+    ///
+    /// This behaves like [`add_from_query_filter`][Self::add_from_query_filter],
+    /// except every matched entity is added via
+    /// [`add_entity_with_descendants`][Self::add_entity_with_descendants]
+    /// rather than just by itself.
+    pub fn add_recursive<F>(&mut self) -> &mut Self
+    where
+        F: ReadOnlyWorldQuery + 'static,
+    {
+        let mut ss = SystemState::<Query<Entity, F>>::new(self.world);
+        let q = ss.get(self.world);
+        let roots: Vec<Entity> = q.iter().collect();
+        for e in roots {
+            self.add_entity_with_descendants(e);
+        }
+        self
+    }
+
     /// Include the specified components on a given entity ID
     ///
     /// The entity ID provided will be added, if it has not been already.
@@ -247,6 +613,88 @@ impl<'w> SceneBuilder<'w> {
         self
     }
 
+    /// Include the specified components on a given entity ID, resolved by type path
+    ///
+    /// Unlike [`add_components_to_entity`][Self::add_components_to_entity], this does
+    /// not require the components to be known at compile time as a [`ComponentList`].
+    /// Instead, each entry in `names` is looked up in the `TypeRegistry` by its
+    /// type path (see `TypeRegistry::get_with_type_path`), making this suitable for
+    /// editors, asset pipelines, or modding tools that only know component names
+    /// at runtime.
+    ///
+    /// The entity ID provided will be added, if it has not been already.
+    ///
+    /// Returns an error if any of the names does not resolve to a registered,
+    /// reflectable component.
+    pub fn add_components_by_name(
+        &mut self,
+        e: Entity,
+        names: &[&str],
+    ) -> Result<&mut Self, UnknownComponent> {
+        let ids = {
+            let registry = self.world.get_resource::<TypeRegistry>().unwrap().read();
+            names
+                .iter()
+                .map(|name| {
+                    let registration = registry
+                        .get_with_type_path(name)
+                        .ok_or_else(|| UnknownComponent::Name((*name).to_string()))?;
+                    self.world
+                        .components()
+                        .get_id(registration.type_id())
+                        .ok_or(UnknownComponent::NotAComponent(registration.type_id()))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        self.insert_component_ids(e, ids);
+        Ok(self)
+    }
+
+    /// Include the specified components on a given entity ID, resolved by `TypeId`
+    ///
+    /// Works just like [`add_components_by_name`][Self::add_components_by_name], but
+    /// resolves each entry in `type_ids` against the `TypeRegistry` by `TypeId`
+    /// instead of by type path.
+    ///
+    /// Returns an error if any of the `TypeId`s does not resolve to a registered,
+    /// reflectable component.
+    pub fn add_components_by_type_id(
+        &mut self,
+        e: Entity,
+        type_ids: &[TypeId],
+    ) -> Result<&mut Self, UnknownComponent> {
+        let ids = {
+            let registry = self.world.get_resource::<TypeRegistry>().unwrap().read();
+            type_ids
+                .iter()
+                .map(|type_id| {
+                    registry
+                        .get(*type_id)
+                        .ok_or(UnknownComponent::TypeId(*type_id))?;
+                    self.world
+                        .components()
+                        .get_id(*type_id)
+                        .ok_or(UnknownComponent::NotAComponent(*type_id))
+                })
+                .collect::<Result<Vec<_>, _>>()?
+        };
+        self.insert_component_ids(e, ids);
+        Ok(self)
+    }
+
+    /// Merge a list of resolved `ComponentId`s into the component selection of `e`
+    ///
+    /// The entity ID provided will be added, if it has not been already.
+    fn insert_component_ids<I: IntoIterator<Item = ComponentId>>(&mut self, e: Entity, ids: I) {
+        if let Some(item) = self.ec.get_mut(&e) {
+            if let ComponentSelection::ByIds(c) = item {
+                c.extend(ids);
+            }
+        } else {
+            self.ec.insert(e, ComponentSelection::ByIds(ids.into_iter().collect()));
+        }
+    }
+
     /// Add specific components to entities that match a query filter
     ///
     /// This method allows you to select entities in a way similar to
@@ -280,12 +728,19 @@ impl<'w> SceneBuilder<'w> {
     /// Build a [`DynamicScene`] with the selected entities and components
     ///
     /// Everything that was added to the builder (using the various `add_*`
-    /// methods) will be included in the scene.
+    /// methods) will be included in the scene, minus any denied components
+    /// (see [`deny_component`][Self::deny_component] and
+    /// [`clear_default_filters`][Self::clear_default_filters]). Entity ids are
+    /// copied verbatim from the source `World` (they are never renumbered or
+    /// compacted).
     ///
-    /// All the relevant data will be copied from the `World` that was provided
-    /// when the [`SceneBuilder`] was created.
+    /// Components on the selected entities may still hold `Entity` fields
+    /// (parents, children, targets, ...) that point outside the selection;
+    /// see [`drop_external_entity_refs`][Self::drop_external_entity_refs] for
+    /// how those are handled.
     pub fn build_scene(&self) -> DynamicScene {
         let type_registry = self.world.get_resource::<TypeRegistry>().unwrap().read();
+        let selected_entities: HashSet<Entity> = self.ec.keys().copied().collect();
 
         let entities = self.ec.iter().map(|(entity, csel)| {
             let get_reflect_by_id = |id|
@@ -296,24 +751,34 @@ impl<'w> SceneBuilder<'w> {
                     .and_then(|rc| rc.reflect(self.world, *entity))
                     .map(|c| c.clone_value());
 
-            let components = match csel {
+            let mut components: Vec<Box<dyn Reflect>> = match csel {
                 ComponentSelection::All => {
                     self.world.entities()
                         .get(*entity)
                         .and_then(|eloc| self.world.archetypes().get(eloc.archetype_id))
                         .into_iter()
                         .flat_map(|a| a.components())
+                        .filter(|id| !self.default_deny.contains(id) && !self.explicit_deny.contains(id))
+                        .filter(|id| self.retain_component(*entity, *id))
                         .filter_map(get_reflect_by_id)
                         .collect()
                 },
                 ComponentSelection::ByIds(ids) => {
                     ids.iter()
                         .cloned()
+                        .filter(|id| !self.explicit_deny.contains(id))
+                        .filter(|id| self.retain_component(*entity, *id))
                         .filter_map(get_reflect_by_id)
                         .collect()
                 },
             };
 
+            if !self.keep_external_refs {
+                for component in &mut components {
+                    scrub_external_entities(&mut **component, &selected_entities);
+                }
+            }
+
             DynamicEntity {
                 entity: entity.id(),
                 components,
@@ -324,6 +789,35 @@ impl<'w> SceneBuilder<'w> {
             entities,
         }
     }
+
+    /// Build a [`DynamicScene`] together with the resources selected via
+    /// [`add_resource`][Self::add_resource] / [`add_resource_by_name`][Self::add_resource_by_name]
+    ///
+    /// The targeted Bevy version's [`DynamicScene`] has no room for resources,
+    /// so they are returned alongside it in a [`SceneWithResources`] instead.
+    pub fn build_scene_with_resources(&self) -> SceneWithResources {
+        let scene = self.build_scene();
+
+        let type_registry = self.world.get_resource::<TypeRegistry>().unwrap().read();
+        let resources = self.resources.iter()
+            .filter(|type_id| !self.deny_resources.contains(*type_id))
+            .filter_map(|type_id| {
+                type_registry.get(*type_id)
+                    .and_then(|reg| reg.data::<ReflectResource>())
+                    .and_then(|rr| rr.reflect(self.world))
+                    .map(|r| r.clone_value())
+            }).collect();
+
+        SceneWithResources { scene, resources }
+    }
+}
+
+/// A [`DynamicScene`] plus the resources collected by a [`SceneBuilder`]
+///
+/// Returned by [`SceneBuilder::build_scene_with_resources`]; see its docs.
+pub struct SceneWithResources {
+    pub scene: DynamicScene,
+    pub resources: Vec<Box<dyn Reflect>>,
 }
 
 pub trait ComponentList {
@@ -355,4 +849,182 @@ all_tuples!(componentlist_impl, 0, 15, T);
 
 #[cfg(test)]
 mod test {
+    use super::*;
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct Link {
+        to: Entity,
+    }
+
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    struct OptLink {
+        to: Option<Entity>,
+    }
+
+    fn world_with_registry() -> World {
+        let mut world = World::new();
+        let registry = TypeRegistry::new();
+        registry.write().register::<Link>();
+        registry.write().register::<OptLink>();
+        world.insert_resource(registry);
+        world
+    }
+
+    #[test]
+    fn add_components_by_name_errors_on_unknown_name() {
+        let mut world = World::new();
+        world.insert_resource(TypeRegistry::new());
+        let e = world.spawn_empty().id();
+
+        let mut builder = SceneBuilder::new(&mut world);
+        let err = builder.add_components_by_name(e, &["does::not::Exist"]).unwrap_err();
+        assert_eq!(err, UnknownComponent::Name("does::not::Exist".to_string()));
+    }
+
+    #[test]
+    fn add_components_by_type_id_errors_on_unknown_type() {
+        let mut world = World::new();
+        world.insert_resource(TypeRegistry::new());
+        let e = world.spawn_empty().id();
+
+        let mut builder = SceneBuilder::new(&mut world);
+        let type_id = TypeId::of::<f32>();
+        let err = builder.add_components_by_type_id(e, &[type_id]).unwrap_err();
+        assert_eq!(err, UnknownComponent::TypeId(type_id));
+    }
+
+    #[test]
+    fn add_components_by_name_resolves_a_registered_component() {
+        let mut world = world_with_registry();
+        world.spawn(Link::default());
+        let e = world.spawn_empty().id();
+
+        let mut builder = SceneBuilder::new(&mut world);
+        let type_path = std::any::type_name::<Link>();
+        builder.add_components_by_name(e, &[type_path])
+            .expect("Link is registered and should resolve by type path");
+    }
+
+    #[test]
+    fn external_entity_refs_are_preserved_by_default() {
+        let mut world = world_with_registry();
+        let outside = world.spawn_empty().id();
+        let inside = world.spawn(Link { to: outside }).id();
+
+        let mut builder = SceneBuilder::new(&mut world);
+        builder.add_entity(inside);
+        let scene = builder.build_scene();
+
+        let link = scene.entities[0].components.iter()
+            .find_map(|c| c.as_any().downcast_ref::<Link>())
+            .expect("Link component missing from scene");
+        assert_eq!(link.to, outside);
+    }
+
+    #[test]
+    fn drop_external_entity_refs_nulls_out_of_selection_refs() {
+        let mut world = world_with_registry();
+        let outside = world.spawn_empty().id();
+        let inside = world.spawn(Link { to: outside }).id();
+
+        let mut builder = SceneBuilder::new(&mut world);
+        builder.add_entity(inside).drop_external_entity_refs();
+        let scene = builder.build_scene();
+
+        let link = scene.entities[0].components.iter()
+            .find_map(|c| c.as_any().downcast_ref::<Link>())
+            .expect("Link component missing from scene");
+        assert_eq!(link.to, Entity::PLACEHOLDER);
+    }
+
+    #[test]
+    fn drop_external_entity_refs_nulls_out_of_selection_refs_inside_an_option() {
+        let mut world = world_with_registry();
+        let outside = world.spawn_empty().id();
+        let inside = world.spawn(OptLink { to: Some(outside) }).id();
+
+        let mut builder = SceneBuilder::new(&mut world);
+        builder.add_entity(inside).drop_external_entity_refs();
+        let scene = builder.build_scene();
+
+        let link = scene.entities[0].components.iter()
+            .find_map(|c| c.as_any().downcast_ref::<OptLink>())
+            .expect("OptLink component missing from scene");
+        assert_eq!(link.to, Some(Entity::PLACEHOLDER));
+    }
+
+    #[test]
+    fn entity_ids_are_copied_verbatim() {
+        let mut world = world_with_registry();
+        let e = world.spawn_empty().id();
+
+        let mut builder = SceneBuilder::new(&mut world);
+        builder.add_entity(e);
+        let scene = builder.build_scene();
+
+        assert_eq!(scene.entities[0].entity, e.id());
+    }
+
+    #[test]
+    fn default_deny_does_not_affect_explicit_selection() {
+        let mut world = World::new();
+        let registry = TypeRegistry::new();
+        registry.write().register::<GlobalTransform>();
+        world.insert_resource(registry);
+
+        let e = world.spawn(GlobalTransform::default()).id();
+        let mut builder = SceneBuilder::new(&mut world);
+        builder.add_components_to_entity::<&GlobalTransform>(e);
+        let scene = builder.build_scene();
+
+        assert_eq!(scene.entities[0].components.len(), 1);
+    }
+
+    #[test]
+    fn explicit_deny_overrides_an_explicit_selection() {
+        let mut world = World::new();
+        let registry = TypeRegistry::new();
+        registry.write().register::<GlobalTransform>();
+        world.insert_resource(registry);
+
+        let e = world.spawn(GlobalTransform::default()).id();
+        let mut builder = SceneBuilder::new(&mut world);
+        builder.deny_component::<GlobalTransform>();
+        builder.add_components_to_entity::<&GlobalTransform>(e);
+        let scene = builder.build_scene();
+
+        assert!(scene.entities[0].components.is_empty());
+    }
+
+    #[derive(Resource, Reflect, Default)]
+    #[reflect(Resource)]
+    struct Score(u32);
+
+    #[test]
+    fn deny_resource_overrides_an_explicit_resource_selection() {
+        let mut world = World::new();
+        let registry = TypeRegistry::new();
+        registry.write().register::<Score>();
+        world.insert_resource(registry);
+        world.insert_resource(Score(7));
+
+        let mut builder = SceneBuilder::new(&mut world);
+        builder.add_resource::<Score>();
+        builder.deny_resource::<Score>();
+        let scene = builder.build_scene_with_resources();
+
+        assert!(scene.resources.is_empty());
+    }
+
+    #[test]
+    fn add_resource_by_name_errors_on_a_non_resource_type() {
+        let mut world = world_with_registry();
+        let type_path = std::any::type_name::<Link>();
+
+        let mut builder = SceneBuilder::new(&mut world);
+        let err = builder.add_resource_by_name(type_path).unwrap_err();
+        assert_eq!(err, UnknownComponent::NotAResource(TypeId::of::<Link>()));
+    }
 }